@@ -0,0 +1,49 @@
+use {
+    axum::{
+        http,
+        response::{IntoResponse, Response},
+    },
+    thiserror::Error,
+};
+
+pub type RpcResult<T> = Result<T, RpcError>;
+
+#[derive(Error, Debug)]
+pub enum RpcError {
+    #[error("Chain not found")]
+    ChainNotFound,
+
+    #[error("Transport error: {0}")]
+    Transport(#[from] hyper::Error),
+
+    #[error("HTTP error: {0}")]
+    Http(#[from] http::Error),
+
+    #[error("WebSocket transport error: {0}")]
+    AxumTungstenite(Box<async_tungstenite::tungstenite::Error>),
+
+    #[error("No quorum reached among providers: {0}")]
+    QuorumNotReached(String),
+
+    #[error("All providers exhausted their retries: {0}")]
+    RetriesExhausted(String),
+
+    #[error("Method `{0}` is not supported by the detected upstream node client")]
+    MethodNotSupported(String),
+
+    #[error("State proof verification failed: {0}")]
+    ProofVerificationFailed(String),
+}
+
+impl IntoResponse for RpcError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            RpcError::ChainNotFound => http::StatusCode::NOT_FOUND,
+            RpcError::QuorumNotReached(_) => http::StatusCode::BAD_GATEWAY,
+            RpcError::MethodNotSupported(_) => http::StatusCode::NOT_IMPLEMENTED,
+            RpcError::ProofVerificationFailed(_) => http::StatusCode::BAD_GATEWAY,
+            _ => http::StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
@@ -1,13 +1,12 @@
 use {
     super::{
         is_internal_error_rpc_code, is_node_error_rpc_message, is_rate_limited_error_rpc_message,
-        Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory, RpcQueryParams,
-        RpcWsProvider, WS_PROXY_TASK_METRICS,
+        ws_multiplex::SubscriptionMultiplexer, Provider, ProviderKind, RateLimited, RpcProvider,
+        RpcProviderFactory, RpcQueryParams, RpcWsProvider, WS_PROXY_TASK_METRICS,
     },
     crate::{
         env::AllnodesConfig,
         error::{RpcError, RpcResult},
-        ws,
     },
     async_trait::async_trait,
     axum::{
@@ -17,7 +16,7 @@ use {
     axum_tungstenite::WebSocketUpgrade,
     hyper::{client::HttpConnector, http, Client, Method},
     hyper_tls::HttpsConnector,
-    std::collections::HashMap,
+    std::{collections::HashMap, sync::Arc},
     tracing::debug,
     wc::future::FutureExt,
 };
@@ -33,6 +32,7 @@ pub struct AllnodesProvider {
 pub struct AllnodesWsProvider {
     pub supported_chains: HashMap<String, String>,
     pub api_key: String,
+    multiplexer: Arc<SubscriptionMultiplexer>,
 }
 
 impl Provider for AllnodesWsProvider {
@@ -57,19 +57,18 @@ impl RpcWsProvider for AllnodesWsProvider {
         ws: WebSocketUpgrade,
         query_params: RpcQueryParams,
     ) -> RpcResult<Response> {
-        let chain = &self
+        let chain = self
             .supported_chains
             .get(&query_params.chain_id)
-            .ok_or(RpcError::ChainNotFound)?;
+            .ok_or(RpcError::ChainNotFound)?
+            .clone();
 
-        let project_id = query_params.project_id;
+        let chain_id = query_params.chain_id;
         let uri = format!("wss://{}.allnodes.me:8546/{}", chain, &self.api_key);
-        let (websocket_provider, _) = async_tungstenite::tokio::connect_async(uri)
-            .await
-            .map_err(|e| RpcError::AxumTungstenite(Box::new(e)))?;
+        let multiplexer = self.multiplexer.clone();
 
         Ok(ws.on_upgrade(move |socket| {
-            ws::proxy(project_id, socket, websocket_provider)
+            super::ws_multiplex::proxy(chain_id, uri, socket, multiplexer)
                 .with_metrics(WS_PROXY_TASK_METRICS.with_name("allnodes"))
         }))
     }
@@ -186,6 +185,7 @@ impl RpcProviderFactory<AllnodesConfig> for AllnodesWsProvider {
         AllnodesWsProvider {
             supported_chains,
             api_key: provider_config.api_key.clone(),
+            multiplexer: Arc::new(SubscriptionMultiplexer::new()),
         }
     }
 }
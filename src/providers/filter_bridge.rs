@@ -0,0 +1,230 @@
+use {
+    super::RpcProvider,
+    crate::error::{RpcError, RpcResult},
+    axum_tungstenite::{Message as ClientMessage, WebSocket, WebSocketUpgrade},
+    axum::response::{IntoResponse, Response},
+    serde_json::{json, Value},
+    std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    },
+    tokio::sync::mpsc,
+    tracing::{debug, warn},
+};
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(250);
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Emulates `eth_subscribe` over an HTTP-only [`RpcProvider`] by installing
+/// an `eth_newBlockFilter`/`eth_newFilter` and polling `eth_getFilterChanges`
+/// on an interval, wrapping each returned item in its own `eth_subscription`
+/// notification frame. Ports ethers-rs's `FilterWatcher` polling stream so subscription
+/// semantics stay uniform whether the backend speaks WebSocket or HTTP.
+pub async fn upgrade(
+    provider: Arc<dyn RpcProvider>,
+    chain_id: String,
+    ws: WebSocketUpgrade,
+) -> RpcResult<Response> {
+    if !provider.supports_caip_chainid(&chain_id) {
+        return Err(RpcError::ChainNotFound);
+    }
+    Ok(ws.on_upgrade(move |socket| proxy(provider, chain_id, socket)))
+}
+
+async fn proxy(provider: Arc<dyn RpcProvider>, chain_id: String, socket: WebSocket) {
+    use futures::{SinkExt, StreamExt};
+
+    let (mut client_sink, mut client_stream) = socket.split();
+    let (notify_sender, mut notify_receiver) = mpsc::unbounded_channel::<ClientMessage>();
+    // Keyed by the subscription id handed to the client, which never
+    // changes; the live upstream filter id (the cell) can change underneath
+    // it if `poll_filter` recreates an evicted filter, so uninstalling must
+    // always read the cell rather than the subscription id itself.
+    let mut pollers: std::collections::HashMap<String, (tokio::task::JoinHandle<()>, Arc<Mutex<String>>)> =
+        std::collections::HashMap::new();
+
+    loop {
+        tokio::select! {
+            notification = notify_receiver.recv() => {
+                let Some(notification) = notification else { break };
+                if client_sink.send(notification).await.is_err() {
+                    break;
+                }
+            }
+            message = client_stream.next() => {
+                let Some(Ok(ClientMessage::Text(text))) = message else { break };
+                let Ok(request) = serde_json::from_str::<Value>(&text) else { continue };
+                let request_id = request.get("id").cloned().unwrap_or(json!(null));
+                let method = request.get("method").and_then(Value::as_str).unwrap_or_default();
+
+                match method {
+                    "eth_subscribe" => {
+                        let params = request.get("params").cloned().unwrap_or(json!([]));
+                        match install_filter(&provider, &chain_id, &params).await {
+                            Ok(filter_id) => {
+                                let subscription_id = filter_id.clone();
+                                let current_filter_id = Arc::new(Mutex::new(filter_id.clone()));
+                                let handle = tokio::spawn(poll_filter(
+                                    provider.clone(),
+                                    chain_id.clone(),
+                                    params,
+                                    filter_id,
+                                    current_filter_id.clone(),
+                                    subscription_id.clone(),
+                                    notify_sender.clone(),
+                                ));
+                                pollers.insert(subscription_id.clone(), (handle, current_filter_id));
+                                let response = json!({"jsonrpc": "2.0", "id": request_id, "result": subscription_id});
+                                if client_sink.send(ClientMessage::Text(response.to_string())).await.is_err() {
+                                    break;
+                                }
+                            }
+                            Err(e) => {
+                                let response = json!({"jsonrpc": "2.0", "id": request_id, "error": {"code": -32603, "message": e.to_string()}});
+                                if client_sink.send(ClientMessage::Text(response.to_string())).await.is_err() {
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                    "eth_unsubscribe" => {
+                        let subscription_id = request
+                            .pointer("/params/0")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .to_string();
+                        if let Some((handle, current_filter_id)) = pollers.remove(&subscription_id) {
+                            handle.abort();
+                            let live_filter_id = current_filter_id.lock().unwrap().clone();
+                            uninstall_filter(&provider, &chain_id, &live_filter_id).await;
+                        }
+                        let response = json!({"jsonrpc": "2.0", "id": request_id, "result": true});
+                        if client_sink.send(ClientMessage::Text(response.to_string())).await.is_err() {
+                            break;
+                        }
+                    }
+                    _ => {
+                        let response = match provider.proxy(&chain_id, serde_json::to_vec(&request).unwrap_or_default().into()).await {
+                            Ok(response) => {
+                                let body = hyper::body::to_bytes(response.into_body()).await.unwrap_or_default();
+                                serde_json::from_slice(&body).unwrap_or(json!({"jsonrpc": "2.0", "id": request_id, "result": null}))
+                            }
+                            Err(e) => json!({"jsonrpc": "2.0", "id": request_id, "error": {"code": -32603, "message": e.to_string()}}),
+                        };
+                        if client_sink.send(ClientMessage::Text(response.to_string())).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    for (handle, current_filter_id) in pollers.into_values() {
+        handle.abort();
+        let live_filter_id = current_filter_id.lock().unwrap().clone();
+        uninstall_filter(&provider, &chain_id, &live_filter_id).await;
+    }
+}
+
+async fn rpc_call(provider: &Arc<dyn RpcProvider>, chain_id: &str, method: &str, params: Value) -> RpcResult<Value> {
+    let request = json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params});
+    let response = provider
+        .proxy(chain_id, serde_json::to_vec(&request).expect("serializable").into())
+        .await?;
+    let body = hyper::body::to_bytes(response.into_body())
+        .await
+        .map_err(RpcError::Transport)?;
+    let parsed: jsonrpc::Response =
+        serde_json::from_slice(&body).map_err(|_| RpcError::RetriesExhausted("malformed filter response".to_string()))?;
+    if let Some(error) = parsed.error {
+        return Err(RpcError::RetriesExhausted(error.message));
+    }
+    serde_json::to_value(parsed.result).map_err(|_| RpcError::RetriesExhausted("malformed filter result".to_string()))
+}
+
+async fn install_filter(provider: &Arc<dyn RpcProvider>, chain_id: &str, subscribe_params: &Value) -> RpcResult<String> {
+    let kind = subscribe_params.get(0).and_then(Value::as_str).unwrap_or("newHeads");
+    let result = match kind {
+        "newHeads" => rpc_call(provider, chain_id, "eth_newBlockFilter", json!([])).await?,
+        "logs" => {
+            let filter = subscribe_params.get(1).cloned().unwrap_or(json!({}));
+            rpc_call(provider, chain_id, "eth_newFilter", json!([filter])).await?
+        }
+        other => return Err(RpcError::RetriesExhausted(format!("unsupported subscription kind: {other}"))),
+    };
+    result
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| RpcError::RetriesExhausted("filter installation did not return an id".to_string()))
+}
+
+async fn uninstall_filter(provider: &Arc<dyn RpcProvider>, chain_id: &str, filter_id: &str) {
+    if let Err(e) = rpc_call(provider, chain_id, "eth_uninstallFilter", json!([filter_id])).await {
+        warn!("failed to uninstall filter {filter_id}: {e}");
+    }
+}
+
+/// Polls `eth_getFilterChanges` for one subscription, sending each returned
+/// change as its own `eth_subscription` notification. Recreates the filter on a
+/// `filter not found` error (the node evicted it after inactivity) and
+/// adapts the poll interval towards the observed rate of non-empty polls.
+/// `current_filter_id` is kept in sync with every recreation so the cleanup
+/// paths in `proxy()` always uninstall the live filter, not the one that was
+/// installed at subscribe time.
+async fn poll_filter(
+    provider: Arc<dyn RpcProvider>,
+    chain_id: String,
+    subscribe_params: Value,
+    mut filter_id: String,
+    current_filter_id: Arc<Mutex<String>>,
+    subscription_id: String,
+    notify_sender: mpsc::UnboundedSender<ClientMessage>,
+) {
+    let mut interval = DEFAULT_POLL_INTERVAL;
+    loop {
+        tokio::time::sleep(interval).await;
+
+        match rpc_call(&provider, &chain_id, "eth_getFilterChanges", json!([filter_id])).await {
+            Ok(changes) => {
+                let is_empty = changes.as_array().map(Vec::is_empty).unwrap_or(true);
+                interval = if is_empty {
+                    (interval + MIN_POLL_INTERVAL / 4).min(MAX_POLL_INTERVAL)
+                } else {
+                    (interval.saturating_sub(MIN_POLL_INTERVAL / 4)).max(MIN_POLL_INTERVAL)
+                };
+
+                // `eth_subscription` carries one item per notification, never
+                // a batch, so each filter-change entry is sent separately.
+                for change in changes.as_array().into_iter().flatten() {
+                    let notification = json!({
+                        "jsonrpc": "2.0",
+                        "method": "eth_subscription",
+                        "params": {"subscription": subscription_id, "result": change},
+                    });
+                    if notify_sender.send(ClientMessage::Text(notification.to_string())).is_err() {
+                        return;
+                    }
+                }
+            }
+            Err(e) if e.to_string().contains("filter not found") => {
+                debug!(%filter_id, "filter expired upstream, recreating");
+                match install_filter(&provider, &chain_id, &subscribe_params).await {
+                    Ok(new_filter_id) => {
+                        *current_filter_id.lock().unwrap() = new_filter_id.clone();
+                        filter_id = new_filter_id;
+                    }
+                    Err(e) => {
+                        warn!("failed to recreate expired filter: {e}");
+                        return;
+                    }
+                }
+            }
+            Err(e) => {
+                warn!("eth_getFilterChanges failed: {e}");
+                return;
+            }
+        }
+    }
+}
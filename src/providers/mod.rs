@@ -0,0 +1,111 @@
+use {
+    crate::error::RpcResult,
+    async_trait::async_trait,
+    axum::response::Response,
+    axum_tungstenite::WebSocketUpgrade,
+    serde::Deserialize,
+    std::fmt,
+};
+
+pub mod allnodes;
+pub mod filter_bridge;
+pub mod mpt;
+pub mod node_client;
+pub mod publicnode;
+pub mod quorum;
+pub mod retry;
+pub mod verify;
+pub mod ws_multiplex;
+
+use node_client::NodeClient;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProviderKind {
+    Allnodes,
+    Publicnode,
+    Quorum,
+    Retry,
+}
+
+impl fmt::Display for ProviderKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProviderKind::Allnodes => write!(f, "Allnodes"),
+            ProviderKind::Publicnode => write!(f, "Publicnode"),
+            ProviderKind::Quorum => write!(f, "Quorum"),
+            ProviderKind::Retry => write!(f, "Retry"),
+        }
+    }
+}
+
+pub trait Provider: std::fmt::Debug + Send + Sync {
+    fn supports_caip_chainid(&self, chain_id: &str) -> bool;
+
+    fn supported_caip_chains(&self) -> Vec<String>;
+
+    fn provider_kind(&self) -> ProviderKind;
+
+    /// Whether this backend can serve `method` given its detected upstream
+    /// node client. Unknown/undetected clients are assumed capable so that
+    /// generic `eth_*`/`net_*`/`web3_*` methods aren't gated by default;
+    /// only client-specific methods (`debug_trace*`, `trace_*`, `txpool_*`)
+    /// actually consult `node_client`.
+    fn supports_method(&self, method: &str, node_client: Option<NodeClient>) -> bool {
+        match node_client {
+            Some(client) => client.supports_method(method),
+            None => true,
+        }
+    }
+}
+
+#[async_trait]
+pub trait RpcProvider: Provider {
+    async fn proxy(&self, chain_id: &str, body: hyper::body::Bytes) -> RpcResult<Response>;
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RpcQueryParams {
+    pub chain_id: String,
+    pub project_id: String,
+}
+
+#[async_trait]
+pub trait RpcWsProvider: Provider {
+    async fn proxy(&self, ws: WebSocketUpgrade, query_params: RpcQueryParams) -> RpcResult<Response>;
+}
+
+#[async_trait]
+pub trait RateLimited {
+    async fn is_rate_limited(&self, response: &mut Response) -> bool
+    where
+        Self: Sized;
+}
+
+pub trait RpcProviderFactory<T> {
+    fn new(provider_config: &T) -> Self;
+}
+
+/// JSON-RPC error codes in the `-32000..-32099` "server error" range are
+/// upstream-node specific rather than protocol-level, so they're the ones
+/// worth inspecting further for rate-limit/node-health classification.
+pub fn is_internal_error_rpc_code(code: i64) -> bool {
+    (-32099..=-32000).contains(&code)
+}
+
+pub fn is_node_error_rpc_message(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("header not found")
+        || message.contains("execution timeout")
+        || message.contains("internal error")
+}
+
+pub fn is_rate_limited_error_rpc_message(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("rate limit")
+        || message.contains("too many requests")
+        || message.contains("quota exceeded")
+        || message.contains("capacity exceeded")
+}
+
+pub static WS_PROXY_TASK_METRICS: once_cell::sync::Lazy<wc::metrics::TaskMetrics> =
+    once_cell::sync::Lazy::new(|| wc::metrics::TaskMetrics::new("ws_proxy"));
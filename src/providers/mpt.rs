@@ -0,0 +1,169 @@
+use sha3::{Digest, Keccak256};
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+fn bytes_to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+/// Decodes the hex-prefix-encoded partial path stored on a leaf/extension
+/// node, returning `(nibbles, is_leaf)`. Errors on a path with no prefix
+/// nibble, which hex-prefix encoding never produces for well-formed input.
+fn decode_hex_prefix(encoded: &[u8]) -> Result<(Vec<u8>, bool), String> {
+    let nibbles = bytes_to_nibbles(encoded);
+    let Some(&prefix) = nibbles.first() else {
+        return Err("hex-prefix-encoded path is empty".to_string());
+    };
+    let is_leaf = prefix & 0b10 != 0;
+    let is_odd = prefix & 0b01 != 0;
+    let start = if is_odd { 1 } else { 2 };
+    Ok((nibbles[start..].to_vec(), is_leaf))
+}
+
+/// Walks a Merkle-Patricia-Trie inclusion/exclusion proof from `root` down
+/// to `key`. Returns `Ok(Some(value))` if `key` is included with `value`,
+/// `Ok(None)` if the proof demonstrates `key` is absent, and `Err` if the
+/// proof doesn't reconcile with `root` at any step.
+///
+/// Embedded (sub-32-byte) child nodes are not supported, since proofs for
+/// the account/storage tries this crate verifies are always deep enough
+/// that every node along the path is hashed.
+pub fn verify_proof(root: [u8; 32], key: &[u8], proof: &[Vec<u8>]) -> Result<Option<Vec<u8>>, String> {
+    let mut nibbles = bytes_to_nibbles(key);
+    let mut expected_hash = root;
+
+    for node_bytes in proof {
+        if keccak256(node_bytes) != expected_hash {
+            return Err("proof node hash does not match expected parent hash".to_string());
+        }
+
+        let node = rlp::Rlp::new(node_bytes);
+        let item_count = node.item_count().map_err(|e| e.to_string())?;
+
+        match item_count {
+            17 => {
+                if nibbles.is_empty() {
+                    let value = node
+                        .at(16)
+                        .map_err(|e| e.to_string())?
+                        .data()
+                        .map_err(|e| e.to_string())?
+                        .to_vec();
+                    return Ok(if value.is_empty() { None } else { Some(value) });
+                }
+                let index = nibbles.remove(0) as usize;
+                let child = node
+                    .at(index)
+                    .map_err(|e| e.to_string())?
+                    .data()
+                    .map_err(|e| e.to_string())?;
+                if child.is_empty() {
+                    return Ok(None);
+                }
+                if child.len() != 32 {
+                    return Err("embedded trie nodes are not supported".to_string());
+                }
+                expected_hash.copy_from_slice(child);
+            }
+            2 => {
+                let path_bytes = node
+                    .at(0)
+                    .map_err(|e| e.to_string())?
+                    .data()
+                    .map_err(|e| e.to_string())?;
+                let (path_nibbles, is_leaf) = decode_hex_prefix(path_bytes)?;
+
+                if nibbles.len() < path_nibbles.len() || nibbles[..path_nibbles.len()] != path_nibbles[..] {
+                    return Ok(None);
+                }
+                nibbles.drain(..path_nibbles.len());
+
+                let value_bytes = node
+                    .at(1)
+                    .map_err(|e| e.to_string())?
+                    .data()
+                    .map_err(|e| e.to_string())?
+                    .to_vec();
+
+                if is_leaf {
+                    return if nibbles.is_empty() {
+                        Ok(Some(value_bytes))
+                    } else {
+                        Ok(None)
+                    };
+                }
+
+                if value_bytes.len() != 32 {
+                    return Err("extension node child must be a hashed reference".to_string());
+                }
+                expected_hash.copy_from_slice(&value_bytes);
+            }
+            _ => return Err("unexpected trie node shape".to_string()),
+        }
+    }
+
+    Err("proof ended before the key was resolved".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Inverse of `decode_hex_prefix`, for building test fixtures.
+    fn encode_hex_prefix(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+        let mut nibbles = nibbles.to_vec();
+        let is_odd = nibbles.len() % 2 == 1;
+        let flag = (if is_leaf { 0b10 } else { 0b00 }) | (if is_odd { 0b01 } else { 0b00 });
+        if !is_odd {
+            nibbles.insert(0, 0);
+        }
+        nibbles.insert(0, flag);
+
+        nibbles
+            .chunks(2)
+            .map(|chunk| (chunk[0] << 4) | chunk[1])
+            .collect()
+    }
+
+    /// Builds a single-leaf trie (the leaf node sits directly at the root)
+    /// for `key` -> `value` and returns `(root, proof)`.
+    fn single_leaf_trie(key: &[u8], value: &[u8]) -> ([u8; 32], Vec<Vec<u8>>) {
+        let path = encode_hex_prefix(&bytes_to_nibbles(key), true);
+        let mut stream = rlp::RlpStream::new_list(2);
+        stream.append(&path);
+        stream.append(&value.to_vec());
+        let node = stream.out();
+        (keccak256(&node), vec![node])
+    }
+
+    #[test]
+    fn verify_proof_resolves_inclusion() {
+        let key = [0x01, 0x23];
+        let value = b"hello".to_vec();
+        let (root, proof) = single_leaf_trie(&key, &value);
+
+        assert_eq!(verify_proof(root, &key, &proof), Ok(Some(value)));
+    }
+
+    #[test]
+    fn verify_proof_resolves_exclusion_on_diverging_path() {
+        let (root, proof) = single_leaf_trie(&[0x01, 0x23], b"hello");
+
+        assert_eq!(verify_proof(root, &[0x04, 0x56], &proof), Ok(None));
+    }
+
+    #[test]
+    fn verify_proof_rejects_tampered_node() {
+        let (root, mut proof) = single_leaf_trie(&[0x01, 0x23], b"hello");
+        proof[0].push(0xff);
+
+        assert!(verify_proof(root, &[0x01, 0x23], &proof).is_err());
+    }
+
+    #[test]
+    fn decode_hex_prefix_rejects_empty_path() {
+        assert!(decode_hex_prefix(&[]).is_err());
+    }
+}
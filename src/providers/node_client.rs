@@ -0,0 +1,191 @@
+use {
+    super::{Provider, ProviderKind, RpcProvider},
+    crate::error::{RpcError, RpcResult},
+    async_trait::async_trait,
+    axum::response::Response,
+    serde_json::{json, Value},
+    std::{
+        collections::HashMap,
+        sync::{Arc, RwLock},
+        time::{Duration, Instant},
+    },
+};
+
+/// Upstream node implementation, detected from the `web3_clientVersion`
+/// response (the substring before the first `/`, lowercased). Reuses the
+/// node-client taxonomy from ethers-rs so client-specific methods can be
+/// routed only to backends that actually implement them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeClient {
+    Geth,
+    Erigon,
+    Nethermind,
+    Besu,
+    OpenEthereum,
+}
+
+impl NodeClient {
+    pub fn from_client_version(client_version: &str) -> Option<Self> {
+        let name = client_version
+            .split('/')
+            .next()
+            .unwrap_or(client_version)
+            .to_lowercase();
+
+        match name.as_str() {
+            "geth" => Some(Self::Geth),
+            "erigon" => Some(Self::Erigon),
+            "nethermind" => Some(Self::Nethermind),
+            "besu" => Some(Self::Besu),
+            "parity-ethereum" | "openethereum" => Some(Self::OpenEthereum),
+            _ => None,
+        }
+    }
+
+    /// Whether this node client implements `method`. Only client-specific
+    /// method families are gated here; everything else (`eth_*`, `net_*`,
+    /// `web3_*`) is considered universally supported.
+    pub fn supports_method(&self, method: &str) -> bool {
+        if method.starts_with("debug_trace") {
+            matches!(self, Self::Geth | Self::Erigon | Self::Nethermind | Self::Besu)
+        } else if method.starts_with("trace_") {
+            matches!(self, Self::Erigon | Self::Nethermind | Self::Besu | Self::OpenEthereum)
+        } else if method.starts_with("txpool_") {
+            matches!(self, Self::Geth | Self::Erigon | Self::Nethermind | Self::Besu)
+        } else {
+            true
+        }
+    }
+}
+
+/// Caches the detected [`NodeClient`] for each `(ProviderKind, chain_id)`
+/// pair so the router doesn't have to issue a `web3_clientVersion` probe on
+/// every request. Entries expire after `ttl` and are re-detected lazily.
+#[derive(Debug)]
+pub struct NodeClientCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<(ProviderKind, String), (NodeClient, Instant)>>,
+}
+
+impl NodeClientCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, provider_kind: ProviderKind, chain_id: &str) -> Option<NodeClient> {
+        let entries = self.entries.read().unwrap();
+        let (client, detected_at) = entries.get(&(provider_kind, chain_id.to_string()))?;
+        if detected_at.elapsed() > self.ttl {
+            return None;
+        }
+        Some(*client)
+    }
+
+    pub fn set(&self, provider_kind: ProviderKind, chain_id: &str, client: NodeClient) {
+        self.entries
+            .write()
+            .unwrap()
+            .insert((provider_kind, chain_id.to_string()), (client, Instant::now()));
+    }
+}
+
+impl Default for NodeClientCache {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(600))
+    }
+}
+
+/// Methods that actually vary by upstream node client. Everything else is
+/// passed through without spending a cache lookup or clientVersion probe.
+const CLIENT_SPECIFIC_PREFIXES: &[&str] = &["debug_trace", "trace_", "txpool_"];
+
+fn is_client_specific(method: &str) -> bool {
+    CLIENT_SPECIFIC_PREFIXES
+        .iter()
+        .any(|prefix| method.starts_with(prefix))
+}
+
+/// Decorates an [`RpcProvider`] so that client-specific methods
+/// (`debug_trace*`/`trace_*`/`txpool_*`) are rejected with
+/// [`RpcError::MethodNotSupported`], instead of an opaque upstream failure,
+/// when the backend's detected [`NodeClient`] does not implement them. When
+/// no client can be detected, the request is forwarded unchecked, as
+/// [`Provider::supports_method`] treats an unknown client as capable. The
+/// detected client is cached per `(ProviderKind, chain_id)`
+/// via `cache`, falling back to a `web3_clientVersion` probe on a miss.
+pub struct CapabilityRoutingProvider {
+    inner: Arc<dyn RpcProvider>,
+    cache: Arc<NodeClientCache>,
+}
+
+impl CapabilityRoutingProvider {
+    pub fn new(inner: Arc<dyn RpcProvider>, cache: Arc<NodeClientCache>) -> Self {
+        Self { inner, cache }
+    }
+
+    async fn detect_node_client(&self, chain_id: &str) -> Option<NodeClient> {
+        if let Some(client) = self.cache.get(self.inner.provider_kind(), chain_id) {
+            return Some(client);
+        }
+
+        let request = json!({"jsonrpc": "2.0", "id": 1, "method": "web3_clientVersion", "params": []});
+        let response = self
+            .inner
+            .proxy(chain_id, serde_json::to_vec(&request).ok()?.into())
+            .await
+            .ok()?;
+        let body = hyper::body::to_bytes(response.into_body()).await.ok()?;
+        let parsed: jsonrpc::Response = serde_json::from_slice(&body).ok()?;
+        let result = serde_json::to_value(&parsed.result).ok()?;
+        let client_version = result.as_str()?;
+        let client = NodeClient::from_client_version(client_version)?;
+
+        self.cache.set(self.inner.provider_kind(), chain_id, client);
+        Some(client)
+    }
+}
+
+impl std::fmt::Debug for CapabilityRoutingProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CapabilityRoutingProvider")
+            .field("inner", &self.inner.provider_kind())
+            .finish()
+    }
+}
+
+impl Provider for CapabilityRoutingProvider {
+    fn supports_caip_chainid(&self, chain_id: &str) -> bool {
+        self.inner.supports_caip_chainid(chain_id)
+    }
+
+    fn supported_caip_chains(&self) -> Vec<String> {
+        self.inner.supported_caip_chains()
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        self.inner.provider_kind()
+    }
+}
+
+#[async_trait]
+impl RpcProvider for CapabilityRoutingProvider {
+    #[tracing::instrument(skip(self, body), fields(provider = %self.provider_kind()), level = "debug")]
+    async fn proxy(&self, chain_id: &str, body: hyper::body::Bytes) -> RpcResult<Response> {
+        let method = serde_json::from_slice::<Value>(&body)
+            .ok()
+            .and_then(|v| v.get("method").and_then(Value::as_str).map(str::to_string))
+            .unwrap_or_default();
+
+        if is_client_specific(&method) {
+            let node_client = self.detect_node_client(chain_id).await;
+            if !self.inner.supports_method(&method, node_client) {
+                return Err(RpcError::MethodNotSupported(method));
+            }
+        }
+
+        self.inner.proxy(chain_id, body).await
+    }
+}
@@ -1,5 +1,8 @@
 use {
-    super::{Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory},
+    super::{
+        filter_bridge, Provider, ProviderKind, RateLimited, RpcProvider, RpcProviderFactory,
+        RpcQueryParams, RpcWsProvider,
+    },
     crate::{
         env::PublicnodeConfig,
         error::{RpcError, RpcResult},
@@ -9,9 +12,10 @@ use {
         http::HeaderValue,
         response::{IntoResponse, Response},
     },
+    axum_tungstenite::WebSocketUpgrade,
     hyper::{client::HttpConnector, http, Client, Method},
     hyper_tls::HttpsConnector,
-    std::collections::HashMap,
+    std::{collections::HashMap, sync::Arc},
 };
 
 #[derive(Debug)]
@@ -85,3 +89,43 @@ impl RpcProviderFactory<PublicnodeConfig> for PublicnodeProvider {
         }
     }
 }
+
+/// Publicnode has no WebSocket endpoint, so `eth_subscribe` is emulated by
+/// polling the HTTP backend's `eth_getFilterChanges` instead of holding open
+/// an upstream socket. Lets the WS route work uniformly across HTTP-only and
+/// WebSocket-native backends.
+#[derive(Debug)]
+pub struct PublicnodeWsProvider {
+    pub inner: Arc<PublicnodeProvider>,
+}
+
+impl Provider for PublicnodeWsProvider {
+    fn supports_caip_chainid(&self, chain_id: &str) -> bool {
+        self.inner.supports_caip_chainid(chain_id)
+    }
+
+    fn supported_caip_chains(&self) -> Vec<String> {
+        self.inner.supported_caip_chains()
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        ProviderKind::Publicnode
+    }
+}
+
+#[async_trait]
+impl RpcWsProvider for PublicnodeWsProvider {
+    #[tracing::instrument(skip_all, fields(provider = %self.provider_kind()), level = "debug")]
+    async fn proxy(&self, ws: WebSocketUpgrade, query_params: RpcQueryParams) -> RpcResult<Response> {
+        filter_bridge::upgrade(self.inner.clone() as Arc<dyn RpcProvider>, query_params.chain_id, ws).await
+    }
+}
+
+impl RpcProviderFactory<PublicnodeConfig> for PublicnodeWsProvider {
+    #[tracing::instrument(level = "debug")]
+    fn new(provider_config: &PublicnodeConfig) -> Self {
+        PublicnodeWsProvider {
+            inner: Arc::new(PublicnodeProvider::new(provider_config)),
+        }
+    }
+}
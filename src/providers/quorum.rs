@@ -0,0 +1,145 @@
+use {
+    super::{Provider, ProviderKind, RpcProvider},
+    crate::error::{RpcError, RpcResult},
+    async_trait::async_trait,
+    axum::response::{IntoResponse, Response},
+    futures::stream::{FuturesUnordered, StreamExt},
+    hyper::http,
+    std::sync::Arc,
+    tracing::debug,
+};
+
+/// A backend participating in a [`QuorumProvider`], with the vote weight
+/// its response carries towards the configured quorum threshold.
+pub struct WeightedProvider {
+    pub provider: Arc<dyn RpcProvider>,
+    pub weight: u32,
+}
+
+impl WeightedProvider {
+    pub fn new(provider: Arc<dyn RpcProvider>, weight: u32) -> Self {
+        Self { provider, weight }
+    }
+}
+
+/// Fans a request out to several [`RpcProvider`]s and only returns a result
+/// once enough of them agree on it, guarding against a single tampered or
+/// faulty upstream. Mirrors ethers-rs's `QuorumProvider`.
+pub struct QuorumProvider {
+    members: Vec<WeightedProvider>,
+    quorum_weight: u32,
+    min_responses: usize,
+}
+
+impl QuorumProvider {
+    pub fn new(members: Vec<WeightedProvider>, quorum_weight: u32, min_responses: usize) -> Self {
+        Self {
+            members,
+            quorum_weight,
+            min_responses,
+        }
+    }
+}
+
+impl std::fmt::Debug for QuorumProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QuorumProvider")
+            .field("members", &self.members.len())
+            .field("quorum_weight", &self.quorum_weight)
+            .field("min_responses", &self.min_responses)
+            .finish()
+    }
+}
+
+impl Provider for QuorumProvider {
+    fn supports_caip_chainid(&self, chain_id: &str) -> bool {
+        self.members
+            .iter()
+            .all(|member| member.provider.supports_caip_chainid(chain_id))
+    }
+
+    fn supported_caip_chains(&self) -> Vec<String> {
+        self.members
+            .first()
+            .map(|member| member.provider.supported_caip_chains())
+            .unwrap_or_default()
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        ProviderKind::Quorum
+    }
+}
+
+struct Vote {
+    result: serde_json::Value,
+    weight: u32,
+    body: hyper::body::Bytes,
+}
+
+#[async_trait]
+impl RpcProvider for QuorumProvider {
+    #[tracing::instrument(skip(self, body), fields(provider = %self.provider_kind()), level = "debug")]
+    async fn proxy(&self, chain_id: &str, body: hyper::body::Bytes) -> RpcResult<Response> {
+        let mut pending: FuturesUnordered<_> = self
+            .members
+            .iter()
+            .map(|member| {
+                let body = body.clone();
+                async move {
+                    let response = member.provider.proxy(chain_id, body).await.ok()?;
+                    if response.status() != http::StatusCode::OK {
+                        return None;
+                    }
+                    let response_body = hyper::body::to_bytes(response.into_body()).await.ok()?;
+                    let parsed = serde_json::from_slice::<jsonrpc::Response>(&response_body).ok()?;
+                    if parsed.error.is_some() {
+                        return None;
+                    }
+                    let result = serde_json::to_value(&parsed.result).ok()?;
+                    Some(Vote {
+                        result,
+                        weight: member.weight,
+                        body: response_body,
+                    })
+                }
+            })
+            .collect();
+
+        // Bucket votes as they arrive and return as soon as any bucket's
+        // weight crosses quorum, instead of waiting on the slowest member.
+        let mut buckets: Vec<(serde_json::Value, u32, hyper::body::Bytes)> = Vec::new();
+        let mut successful_votes = 0usize;
+
+        while let Some(vote) = pending.next().await {
+            let Some(vote) = vote else { continue };
+            successful_votes += 1;
+
+            if let Some(bucket) = buckets.iter_mut().find(|(result, _, _)| *result == vote.result) {
+                bucket.1 += vote.weight;
+            } else {
+                buckets.push((vote.result, vote.weight, vote.body));
+            }
+
+            if successful_votes >= self.min_responses {
+                if let Some((_, _, body)) = buckets.iter().find(|(_, weight, _)| *weight >= self.quorum_weight) {
+                    return Ok((http::StatusCode::OK, body.clone()).into_response());
+                }
+            }
+        }
+
+        if successful_votes < self.min_responses {
+            debug!(
+                "Quorum: only {successful_votes} of {} required providers responded successfully",
+                self.min_responses
+            );
+            return Err(RpcError::QuorumNotReached(format!(
+                "only {successful_votes} successful responses, {} required",
+                self.min_responses
+            )));
+        }
+
+        Err(RpcError::QuorumNotReached(
+            "providers disagreed on the result, no bucket reached quorum".to_string(),
+        ))
+    }
+}
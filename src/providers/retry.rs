@@ -0,0 +1,242 @@
+use {
+    super::{is_rate_limited_error_rpc_message, Provider, ProviderKind, RpcProvider},
+    crate::error::{RpcError, RpcResult},
+    async_trait::async_trait,
+    axum::response::{IntoResponse, Response},
+    hyper::http,
+    rand::Rng,
+    std::{sync::Arc, time::Duration},
+    tokio::time::sleep,
+    tracing::debug,
+};
+
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Decorates an [`RpcProvider`] with rate-limit-aware retries and failover,
+/// importing ethers-rs's `HttpRateLimitRetryPolicy`/`RetryClient` behavior
+/// into the proxy pipeline. `providers` is tried in order: each backend is
+/// retried with backoff on its own rate limit before moving on to the next.
+/// Only idempotent read methods move on to the next backend; any other
+/// method returns the error once the first backend's attempts are used up.
+pub struct RetryProvider {
+    providers: Vec<Arc<dyn RpcProvider>>,
+    config: RetryConfig,
+}
+
+impl RetryProvider {
+    pub fn new(providers: Vec<Arc<dyn RpcProvider>>, config: RetryConfig) -> Self {
+        Self { providers, config }
+    }
+
+    /// Computes the delay before the next attempt: a `Retry-After` header
+    /// takes priority (seconds or HTTP-date, capped at `max_delay`), otherwise
+    /// exponential backoff capped at `max_delay` plus up to a quarter of that
+    /// again as random jitter.
+    fn backoff(&self, response: &Response, attempt: u32) -> Duration {
+        if let Some(retry_after) = parse_retry_after(response) {
+            return retry_after.min(self.config.max_delay);
+        }
+        self.exponential_backoff(attempt)
+    }
+
+    /// Backoff for failures with no response to read a `Retry-After` header
+    /// from, e.g. a transport-level error.
+    fn exponential_backoff(&self, attempt: u32) -> Duration {
+        let exponential = self.config.base_delay * 2u32.saturating_pow(attempt);
+        let capped = exponential.min(self.config.max_delay);
+        let jitter_ms = rand::thread_rng().gen_range(0..=capped.as_millis() as u64 / 4 + 1);
+        capped + Duration::from_millis(jitter_ms)
+    }
+}
+
+fn parse_retry_after(response: &Response) -> Option<Duration> {
+    let header = response.headers().get(http::header::RETRY_AFTER)?;
+    let value = header.to_str().ok()?;
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let retry_at = httpdate::parse_http_date(value).ok()?;
+    retry_at
+        .duration_since(std::time::SystemTime::now())
+        .ok()
+}
+
+/// Methods safe to retry against a *different* backend after one backend's
+/// retries are exhausted. Failover must stay scoped to idempotent reads —
+/// replaying a write like `eth_sendRawTransaction` against a second backend
+/// could double-submit it.
+const IDEMPOTENT_READ_METHODS: &[&str] = &[
+    "eth_call",
+    "eth_chainId",
+    "eth_blockNumber",
+    "eth_getBalance",
+    "eth_getCode",
+    "eth_getStorageAt",
+    "eth_getTransactionCount",
+    "eth_getTransactionByHash",
+    "eth_getTransactionReceipt",
+    "eth_getBlockByHash",
+    "eth_getBlockByNumber",
+    "eth_getLogs",
+    "eth_getProof",
+    "eth_gasPrice",
+    "eth_estimateGas",
+    "eth_feeHistory",
+    "net_version",
+    "web3_clientVersion",
+];
+
+fn is_idempotent_read_method(method: &str) -> bool {
+    IDEMPOTENT_READ_METHODS.contains(&method)
+}
+
+fn is_retryable(status: http::StatusCode, body: &[u8]) -> bool {
+    if status == http::StatusCode::TOO_MANY_REQUESTS {
+        return true;
+    }
+    if let Ok(json_response) = serde_json::from_slice::<jsonrpc::Response>(body) {
+        if let Some(error) = &json_response.error {
+            return is_rate_limited_error_rpc_message(&error.message);
+        }
+    }
+    false
+}
+
+impl std::fmt::Debug for RetryProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryProvider")
+            .field("providers", &self.providers.len())
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl Provider for RetryProvider {
+    fn supports_caip_chainid(&self, chain_id: &str) -> bool {
+        self.providers
+            .iter()
+            .any(|provider| provider.supports_caip_chainid(chain_id))
+    }
+
+    fn supported_caip_chains(&self) -> Vec<String> {
+        self.providers
+            .first()
+            .map(|provider| provider.supported_caip_chains())
+            .unwrap_or_default()
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        ProviderKind::Retry
+    }
+}
+
+#[async_trait]
+impl RpcProvider for RetryProvider {
+    #[tracing::instrument(skip(self, body), fields(provider = %self.provider_kind(), retry_count = tracing::field::Empty), level = "debug")]
+    async fn proxy(&self, chain_id: &str, body: hyper::body::Bytes) -> RpcResult<Response> {
+        if !self.supports_caip_chainid(chain_id) {
+            return Err(RpcError::ChainNotFound);
+        }
+
+        let method = serde_json::from_slice::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|v| v.get("method").and_then(|m| m.as_str()).map(str::to_string))
+            .unwrap_or_default();
+        let can_fail_over = is_idempotent_read_method(&method);
+
+        let mut retry_count = 0u32;
+        let mut last_error = None;
+
+        for provider in self
+            .providers
+            .iter()
+            .filter(|provider| provider.supports_caip_chainid(chain_id))
+        {
+            for attempt in 0..self.config.max_attempts {
+                let started_at = std::time::Instant::now();
+                let response = match provider.proxy(chain_id, body.clone()).await {
+                    Ok(response) => response,
+                    Err(e) => {
+                        // A transport-level failure (e.g. a dropped
+                        // connection) is just as retryable/failover-eligible
+                        // as a rate-limited HTTP response; short-circuiting
+                        // with `?` here would skip both the retry loop and
+                        // failover for every backend hiccup.
+                        if attempt + 1 < self.config.max_attempts {
+                            let delay = self.exponential_backoff(attempt);
+                            retry_count += 1;
+                            debug!(%chain_id, attempt, retry_count, ?delay, error = %e, "retrying after transport error");
+                            tracing::Span::current().record("retry_count", retry_count);
+                            sleep(delay).await;
+                            continue;
+                        }
+
+                        if !can_fail_over {
+                            return Err(e);
+                        }
+                        last_error = Some(e);
+                        break;
+                    }
+                };
+                let latency = started_at.elapsed();
+                let status = response.status();
+
+                let (parts, response_body) = response.into_parts();
+                let response_body = hyper::body::to_bytes(response_body).await?;
+
+                if is_retryable(status, &response_body) && attempt + 1 < self.config.max_attempts {
+                    let rebuilt = Response::from_parts(parts, response_body.into());
+                    let delay = self.backoff(&rebuilt, attempt);
+                    retry_count += 1;
+                    debug!(
+                        %chain_id,
+                        attempt,
+                        retry_count,
+                        ?latency,
+                        ?delay,
+                        "retrying rate-limited provider"
+                    );
+                    tracing::Span::current().record("retry_count", retry_count);
+                    sleep(delay).await;
+                    continue;
+                }
+
+                if is_retryable(status, &response_body) {
+                    let error = RpcError::RetriesExhausted(format!(
+                        "provider {} exhausted {} attempts",
+                        provider.provider_kind(),
+                        self.config.max_attempts
+                    ));
+                    if !can_fail_over {
+                        return Err(error);
+                    }
+                    last_error = Some(error);
+                    break;
+                }
+
+                return Ok((status, response_body).into_response());
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            RpcError::RetriesExhausted("no provider attempted this request".to_string())
+        }))
+    }
+}
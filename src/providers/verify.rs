@@ -0,0 +1,401 @@
+use {
+    super::{mpt, Provider, ProviderKind, RpcProvider},
+    crate::error::{RpcError, RpcResult},
+    async_trait::async_trait,
+    axum::response::{IntoResponse, Response},
+    serde_json::{json, Value},
+    std::{
+        collections::HashMap,
+        sync::{Arc, RwLock},
+        time::{Duration, Instant},
+    },
+};
+
+const VERIFIABLE_METHODS: &[&str] = &[
+    "eth_getBalance",
+    "eth_getStorageAt",
+    "eth_getCode",
+    "eth_getTransactionCount",
+];
+
+struct TrustedHeader {
+    state_root: [u8; 32],
+    /// Hex-encoded quantity of the finalized block the root was fetched at,
+    /// so the companion `eth_getProof` call can be pinned to the exact same
+    /// block rather than whatever block tag the client's own request used
+    /// (which on a live chain is almost always a later, non-finalized block
+    /// with a different state root).
+    block_number: String,
+    fetched_at: Instant,
+}
+
+/// Tracks, per chain, the latest finalized header's state root so state
+/// reads can be cross-checked without fully trusting the backend that
+/// served them. Brings openethereum-style trusted-header tracking into the
+/// crate as the root of trust for [`VerifyingProvider`].
+pub struct TrustedHeaderStore {
+    refresh_interval: Duration,
+    weak_subjectivity_checkpoint: Option<[u8; 32]>,
+    headers: RwLock<HashMap<String, TrustedHeader>>,
+}
+
+impl TrustedHeaderStore {
+    pub fn new(refresh_interval: Duration, weak_subjectivity_checkpoint: Option<[u8; 32]>) -> Self {
+        Self {
+            refresh_interval,
+            weak_subjectivity_checkpoint,
+            headers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the trusted finalized block's state root and its own block
+    /// number, refreshing from `provider` if the cached header has expired.
+    async fn trusted_header(&self, provider: &Arc<dyn RpcProvider>, chain_id: &str) -> RpcResult<([u8; 32], String)> {
+        if let Some(header) = self.headers.read().unwrap().get(chain_id) {
+            if header.fetched_at.elapsed() < self.refresh_interval {
+                return Ok((header.state_root, header.block_number.clone()));
+            }
+        }
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_getBlockByNumber",
+            "params": ["finalized", false],
+        });
+        let response = provider
+            .proxy(chain_id, serde_json::to_vec(&request).expect("serializable").into())
+            .await?;
+        let body = hyper::body::to_bytes(response.into_body())
+            .await
+            .map_err(RpcError::Transport)?;
+        let parsed: Value = serde_json::from_slice(&body)
+            .map_err(|_| RpcError::ProofVerificationFailed("malformed finalized block response".to_string()))?;
+        let block = parsed
+            .get("result")
+            .filter(|v| !v.is_null())
+            .ok_or_else(|| RpcError::ProofVerificationFailed("no finalized block available".to_string()))?;
+
+        let state_root = hex_to_array(block.get("stateRoot").and_then(Value::as_str).unwrap_or_default())?;
+        let block_hash = hex_to_array(block.get("hash").and_then(Value::as_str).unwrap_or_default())?;
+        let block_number = block
+            .get("number")
+            .and_then(Value::as_str)
+            .ok_or_else(|| RpcError::ProofVerificationFailed("finalized block carried no number".to_string()))?
+            .to_string();
+
+        if let Some(checkpoint) = self.weak_subjectivity_checkpoint {
+            if self.headers.read().unwrap().get(chain_id).is_none() && block_hash != checkpoint {
+                return Err(RpcError::ProofVerificationFailed(format!(
+                    "finalized header {block_hash:?} does not match the configured weak-subjectivity checkpoint"
+                )));
+            }
+        }
+
+        self.headers.write().unwrap().insert(
+            chain_id.to_string(),
+            TrustedHeader {
+                state_root,
+                block_number: block_number.clone(),
+                fetched_at: Instant::now(),
+            },
+        );
+        Ok((state_root, block_number))
+    }
+}
+
+/// Wraps any [`RpcProvider`] and, for state-reading methods, cross-checks
+/// the returned value against an `eth_getProof` (EIP-1186) Merkle-Patricia
+/// inclusion proof rooted at a [`TrustedHeaderStore`] state root before
+/// trusting it. Brings helios-style light-client verification into the
+/// crate as an optional, cross-cutting trust boundary over the proxy path.
+pub struct VerifyingProvider {
+    inner: Arc<dyn RpcProvider>,
+    headers: Arc<TrustedHeaderStore>,
+}
+
+impl VerifyingProvider {
+    pub fn new(inner: Arc<dyn RpcProvider>, headers: Arc<TrustedHeaderStore>) -> Self {
+        Self { inner, headers }
+    }
+
+    async fn verify(&self, chain_id: &str, method: &str, request: &Value, response_body: &[u8]) -> RpcResult<()> {
+        let (state_root, block_number) = self.headers.trusted_header(&self.inner, chain_id).await?;
+
+        let response: Value = serde_json::from_slice(response_body)
+            .map_err(|_| RpcError::ProofVerificationFailed("malformed response".to_string()))?;
+        let result = response
+            .get("result")
+            .ok_or_else(|| RpcError::ProofVerificationFailed("response carried no result".to_string()))?;
+
+        let address = request
+            .pointer("/params/0")
+            .and_then(Value::as_str)
+            .ok_or_else(|| RpcError::ProofVerificationFailed("missing address parameter".to_string()))?;
+        let storage_keys = if method == "eth_getStorageAt" {
+            let slot = request
+                .pointer("/params/1")
+                .and_then(Value::as_str)
+                .ok_or_else(|| RpcError::ProofVerificationFailed("missing storage slot".to_string()))?;
+            json!([slot])
+        } else {
+            json!([])
+        };
+
+        // Pinned to the trusted header's own block number, not whatever
+        // block tag the client's request used: "finalized" lags "latest" by
+        // design, so proving against the client's block would almost always
+        // check a different state root than `state_root` and fail spuriously.
+        let proof_request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_getProof",
+            "params": [address, storage_keys, block_number],
+        });
+        let proof_response = self
+            .inner
+            .proxy(chain_id, serde_json::to_vec(&proof_request).expect("serializable").into())
+            .await?;
+        let proof_body = hyper::body::to_bytes(proof_response.into_body())
+            .await
+            .map_err(RpcError::Transport)?;
+        let proof: Value = serde_json::from_slice(&proof_body)
+            .map_err(|_| RpcError::ProofVerificationFailed("malformed eth_getProof response".to_string()))?;
+        let proof = proof
+            .get("result")
+            .ok_or_else(|| RpcError::ProofVerificationFailed("eth_getProof carried no result".to_string()))?;
+
+        let address_bytes = hex_to_bytes(address);
+        let account_key = mpt::keccak256(&address_bytes);
+        let account_proof = decode_proof_nodes(proof.get("accountProof"))?;
+        let account_rlp =
+            mpt::verify_proof(state_root, &account_key, &account_proof).map_err(RpcError::ProofVerificationFailed)?;
+
+        let Some(account_rlp) = account_rlp else {
+            // Exclusion proof: no account at this address, so only a
+            // zero-valued read reconciles with it.
+            return if is_zero_result(result) {
+                Ok(())
+            } else {
+                Err(RpcError::ProofVerificationFailed(
+                    "account proof shows no account but a non-zero value was returned".to_string(),
+                ))
+            };
+        };
+
+        let account = rlp::Rlp::new(&account_rlp);
+        let proven_nonce = account
+            .at(0)
+            .and_then(|f| f.data())
+            .map_err(|e| RpcError::ProofVerificationFailed(e.to_string()))?;
+        let proven_balance = account
+            .at(1)
+            .and_then(|f| f.data())
+            .map_err(|e| RpcError::ProofVerificationFailed(e.to_string()))?;
+        let proven_storage_root = account
+            .at(2)
+            .and_then(|f| f.data())
+            .map_err(|e| RpcError::ProofVerificationFailed(e.to_string()))?;
+        let proven_code_hash = account
+            .at(3)
+            .and_then(|f| f.data())
+            .map_err(|e| RpcError::ProofVerificationFailed(e.to_string()))?;
+
+        if !bytes_match_hex(proof.get("nonce").and_then(Value::as_str).unwrap_or_default(), proven_nonce) {
+            return Err(RpcError::ProofVerificationFailed("nonce does not match account proof".to_string()));
+        }
+        if !bytes_match_hex(proof.get("balance").and_then(Value::as_str).unwrap_or_default(), proven_balance) {
+            return Err(RpcError::ProofVerificationFailed("balance does not match account proof".to_string()));
+        }
+        if !bytes_match_hex(proof.get("codeHash").and_then(Value::as_str).unwrap_or_default(), proven_code_hash) {
+            return Err(RpcError::ProofVerificationFailed("codeHash does not match account proof".to_string()));
+        }
+        if !bytes_match_hex(
+            proof.get("storageHash").and_then(Value::as_str).unwrap_or_default(),
+            proven_storage_root,
+        ) {
+            return Err(RpcError::ProofVerificationFailed("storageHash does not match account proof".to_string()));
+        }
+
+        match method {
+            "eth_getBalance" => {
+                if !value_matches_bytes(result, proven_balance) {
+                    return Err(RpcError::ProofVerificationFailed("returned balance does not match proof".to_string()));
+                }
+            }
+            "eth_getTransactionCount" => {
+                if !value_matches_bytes(result, proven_nonce) {
+                    return Err(RpcError::ProofVerificationFailed("returned nonce does not match proof".to_string()));
+                }
+            }
+            "eth_getCode" => {
+                let code_bytes = hex_to_bytes(result.as_str().unwrap_or_default());
+                if mpt::keccak256(&code_bytes) != proven_code_hash {
+                    return Err(RpcError::ProofVerificationFailed(
+                        "returned code does not hash to the proven codeHash".to_string(),
+                    ));
+                }
+            }
+            "eth_getStorageAt" => {
+                if proven_storage_root.len() != 32 {
+                    return Err(RpcError::ProofVerificationFailed(
+                        "account proof's storageRoot is not 32 bytes".to_string(),
+                    ));
+                }
+                let mut storage_root = [0u8; 32];
+                storage_root.copy_from_slice(proven_storage_root);
+
+                let slot = request.pointer("/params/1").and_then(Value::as_str).unwrap_or_default();
+                let slot_key = mpt::keccak256(&left_pad32(&hex_to_bytes(slot)));
+                let storage_proof = decode_proof_nodes(
+                    proof
+                        .get("storageProof")
+                        .and_then(Value::as_array)
+                        .and_then(|entries| entries.first())
+                        .and_then(|entry| entry.get("proof")),
+                )?;
+                let storage_rlp = mpt::verify_proof(storage_root, &slot_key, &storage_proof)
+                    .map_err(RpcError::ProofVerificationFailed)?;
+                let proven_value = match storage_rlp {
+                    Some(bytes) => rlp::Rlp::new(&bytes)
+                        .data()
+                        .map(<[u8]>::to_vec)
+                        .unwrap_or_default(),
+                    None => Vec::new(),
+                };
+                if !value_matches_bytes(result, &proven_value) {
+                    return Err(RpcError::ProofVerificationFailed(
+                        "returned storage value does not match proof".to_string(),
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for VerifyingProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VerifyingProvider")
+            .field("inner", &self.inner.provider_kind())
+            .finish()
+    }
+}
+
+impl Provider for VerifyingProvider {
+    fn supports_caip_chainid(&self, chain_id: &str) -> bool {
+        self.inner.supports_caip_chainid(chain_id)
+    }
+
+    fn supported_caip_chains(&self) -> Vec<String> {
+        self.inner.supported_caip_chains()
+    }
+
+    fn provider_kind(&self) -> ProviderKind {
+        self.inner.provider_kind()
+    }
+}
+
+#[async_trait]
+impl RpcProvider for VerifyingProvider {
+    #[tracing::instrument(skip(self, body), fields(provider = %self.provider_kind()), level = "debug")]
+    async fn proxy(&self, chain_id: &str, body: hyper::body::Bytes) -> RpcResult<Response> {
+        let request: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+        let method = request.get("method").and_then(Value::as_str).unwrap_or_default();
+
+        if !VERIFIABLE_METHODS.contains(&method) {
+            return self.inner.proxy(chain_id, body).await;
+        }
+
+        let response = self.inner.proxy(chain_id, body).await?;
+        let status = response.status();
+        let response_body = hyper::body::to_bytes(response.into_body())
+            .await
+            .map_err(RpcError::Transport)?;
+
+        // A JSON-RPC error response (bad params, upstream rate limit, ...)
+        // isn't something to verify a proof against; pass it through as-is
+        // rather than reporting a misleading verification failure.
+        let has_error = serde_json::from_slice::<Value>(&response_body)
+            .map(|v| v.get("error").is_some())
+            .unwrap_or(false);
+        if !has_error {
+            self.verify(chain_id, method, &request, &response_body).await?;
+        }
+
+        let mut response = (status, response_body).into_response();
+        response.headers_mut().insert(
+            "Content-Type",
+            axum::http::HeaderValue::from_static("application/json"),
+        );
+        Ok(response)
+    }
+}
+
+fn is_zero_result(result: &Value) -> bool {
+    match result.as_str() {
+        Some(s) => hex_to_bytes(s).iter().all(|&b| b == 0),
+        None => false,
+    }
+}
+
+fn hex_to_bytes(value: &str) -> Vec<u8> {
+    let trimmed = value.trim_start_matches("0x");
+    let padded = if trimmed.len() % 2 == 1 {
+        format!("0{trimmed}")
+    } else {
+        trimmed.to_string()
+    };
+    hex::decode(padded).unwrap_or_default()
+}
+
+fn hex_to_array(value: &str) -> RpcResult<[u8; 32]> {
+    let bytes = hex_to_bytes(value);
+    if bytes.len() != 32 {
+        return Err(RpcError::ProofVerificationFailed(format!("expected a 32-byte hash, got: {value}")));
+    }
+    let mut array = [0u8; 32];
+    array.copy_from_slice(&bytes);
+    Ok(array)
+}
+
+fn left_pad32(bytes: &[u8]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    if bytes.len() <= 32 {
+        out[32 - bytes.len()..].copy_from_slice(bytes);
+    }
+    out
+}
+
+fn normalize(bytes: &[u8]) -> &[u8] {
+    let first_nonzero = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+    &bytes[first_nonzero..]
+}
+
+fn bytes_match_hex(hex_str: &str, bytes: &[u8]) -> bool {
+    normalize(&hex_to_bytes(hex_str)) == normalize(bytes)
+}
+
+fn value_matches_bytes(value: &Value, bytes: &[u8]) -> bool {
+    match value.as_str() {
+        Some(s) => normalize(&hex_to_bytes(s)) == normalize(bytes),
+        None => false,
+    }
+}
+
+fn decode_proof_nodes(value: Option<&Value>) -> RpcResult<Vec<Vec<u8>>> {
+    let entries = value
+        .and_then(Value::as_array)
+        .ok_or_else(|| RpcError::ProofVerificationFailed("eth_getProof response is missing a proof array".to_string()))?;
+    entries
+        .iter()
+        .map(|entry| {
+            entry
+                .as_str()
+                .map(hex_to_bytes)
+                .ok_or_else(|| RpcError::ProofVerificationFailed("proof node is not a hex string".to_string()))
+        })
+        .collect()
+}
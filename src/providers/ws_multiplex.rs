@@ -0,0 +1,390 @@
+use {
+    crate::error::{RpcError, RpcResult},
+    async_tungstenite::{tokio::ConnectStream, tungstenite::Message as UpstreamMessage, WebSocketStream},
+    axum_tungstenite::{Message as ClientMessage, WebSocket},
+    futures::{stream::SplitSink, SinkExt, StreamExt},
+    serde_json::{json, Value},
+    std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+    },
+    tokio::sync::{mpsc, oneshot, Mutex},
+    tracing::{debug, warn},
+};
+
+/// Normalizes `eth_subscribe` params into the dedup key used to decide
+/// whether two downstream clients can share one upstream subscription
+/// (e.g. `["newHeads"]`, or `["logs", {<filter>}]`). `serde_json::Value`
+/// compares object fields independent of key order, so plain canonical
+/// serialization is sufficient here.
+fn subscription_key(params: &Value) -> String {
+    params.to_string()
+}
+
+type UpstreamSink = SplitSink<WebSocketStream<ConnectStream>, UpstreamMessage>;
+
+struct UpstreamSubscription {
+    /// The `result` of the upstream `eth_subscribe` call, i.e. the upstream
+    /// subscription id notifications are tagged with.
+    upstream_id: String,
+    params: Value,
+    subscribers: HashMap<u64, mpsc::UnboundedSender<ClientMessage>>,
+}
+
+struct UpstreamChain {
+    sink: Mutex<UpstreamSink>,
+    /// Keyed by [`subscription_key`].
+    subscriptions: Mutex<HashMap<String, UpstreamSubscription>>,
+    /// Reverse index from the upstream subscription id to its dedup key, so
+    /// incoming `eth_subscription` notifications can be routed in O(1).
+    by_upstream_id: Mutex<HashMap<String, String>>,
+    /// Non-subscription requests awaiting their response, keyed by the id
+    /// they were sent upstream with.
+    pending: Mutex<HashMap<u64, oneshot::Sender<Value>>>,
+}
+
+/// Shares one upstream WebSocket (and its `eth_subscribe` subscriptions) per
+/// chain across every downstream client that asks for the same thing,
+/// reference-counting so `eth_unsubscribe` only fires once the last
+/// subscriber drops. Mirrors ethers-rs's pubsub `SubscriptionStream` model.
+#[derive(Default)]
+pub struct SubscriptionMultiplexer {
+    chains: Mutex<HashMap<String, Arc<UpstreamChain>>>,
+    next_id: AtomicU64,
+}
+
+impl SubscriptionMultiplexer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    async fn chain(&self, chain_id: &str, upstream_uri: &str) -> RpcResult<Arc<UpstreamChain>> {
+        if let Some(chain) = self.chains.lock().await.get(chain_id) {
+            return Ok(chain.clone());
+        }
+
+        // The handshake below must not hold `chains` locked: this is the
+        // single multiplexer-wide lock, so a slow (or hung) connect for one
+        // chain would otherwise block subscribe()/unsubscribe()/request()
+        // for every other already-connected chain too.
+        let (upstream, _) = async_tungstenite::tokio::connect_async(upstream_uri)
+            .await
+            .map_err(|e| RpcError::AxumTungstenite(Box::new(e)))?;
+        let (sink, stream) = upstream.split();
+
+        let chain = Arc::new(UpstreamChain {
+            sink: Mutex::new(sink),
+            subscriptions: Mutex::new(HashMap::new()),
+            by_upstream_id: Mutex::new(HashMap::new()),
+            pending: Mutex::new(HashMap::new()),
+        });
+
+        let mut chains = self.chains.lock().await;
+        if let Some(existing) = chains.get(chain_id) {
+            // Another task's connect_async raced ours and won; drop ours.
+            return Ok(existing.clone());
+        }
+        tokio::spawn(read_upstream_loop(chain.clone(), stream));
+        chains.insert(chain_id.to_string(), chain.clone());
+        Ok(chain)
+    }
+
+    /// Attaches `client_sender` to the shared subscription for `params`,
+    /// creating it upstream if this is the first subscriber. Returns the
+    /// per-client subscriber id, used later to unsubscribe.
+    pub async fn subscribe(
+        &self,
+        chain_id: &str,
+        upstream_uri: &str,
+        params: Value,
+        client_sender: mpsc::UnboundedSender<ClientMessage>,
+    ) -> RpcResult<u64> {
+        let chain = self.chain(chain_id, upstream_uri).await?;
+        let client_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let key = subscription_key(&params);
+
+        // Check-then-insert happens under one lock acquisition so two
+        // concurrent subscribe() calls for the same new `params` can't both
+        // see "no existing subscription" and both send a duplicate upstream
+        // `eth_subscribe` (the second of which would never get linked by
+        // `link_upstream_id` and would leak forever).
+        let mut subscriptions = chain.subscriptions.lock().await;
+        if let Some(subscription) = subscriptions.get_mut(&key) {
+            subscription.subscribers.insert(client_id, client_sender);
+            debug!(chain_id, %key, subscribers = subscription.subscribers.len(), "attached to existing upstream subscription");
+            return Ok(client_id);
+        }
+
+        let mut subscribers = HashMap::new();
+        subscribers.insert(client_id, client_sender);
+        subscriptions.insert(
+            key,
+            UpstreamSubscription {
+                upstream_id: String::new(),
+                params: params.clone(),
+                subscribers,
+            },
+        );
+        drop(subscriptions);
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": client_id,
+            "method": "eth_subscribe",
+            "params": params,
+        });
+        let sent = chain
+            .sink
+            .lock()
+            .await
+            .send(UpstreamMessage::Text(request.to_string()))
+            .await;
+
+        if let Err(e) = sent {
+            // The upstream `eth_subscribe` never went out, so the placeholder
+            // inserted above would otherwise sit forever with an empty
+            // `upstream_id` that `link_upstream_id` can never fill in.
+            chain.subscriptions.lock().await.remove(&key);
+            return Err(RpcError::AxumTungstenite(Box::new(e)));
+        }
+
+        Ok(client_id)
+    }
+
+    /// Detaches `client_id` from `params`'s subscription; sends the upstream
+    /// `eth_unsubscribe` once it was the last subscriber.
+    pub async fn unsubscribe(&self, chain_id: &str, params: &Value, client_id: u64) {
+        let chain = {
+            let chains = self.chains.lock().await;
+            match chains.get(chain_id) {
+                Some(chain) => chain.clone(),
+                None => return,
+            }
+        };
+
+        let key = subscription_key(params);
+        let upstream_id = {
+            let mut subscriptions = chain.subscriptions.lock().await;
+            let Some(subscription) = subscriptions.get_mut(&key) else {
+                return;
+            };
+            subscription.subscribers.remove(&client_id);
+            if !subscription.subscribers.is_empty() {
+                return;
+            }
+            let subscription = subscriptions.remove(&key).expect("just looked up");
+            subscription.upstream_id
+        };
+
+        chain.by_upstream_id.lock().await.remove(&upstream_id);
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": "eth_unsubscribe",
+            "params": [upstream_id],
+        });
+        if let Err(e) = chain
+            .sink
+            .lock()
+            .await
+            .send(UpstreamMessage::Text(request.to_string()))
+            .await
+        {
+            warn!("failed to send eth_unsubscribe to upstream: {e}");
+        }
+    }
+
+    /// Passes a non-subscription request straight through to the shared
+    /// upstream connection and returns its response, with the client's
+    /// original `id` restored (the multiplexer's own id is only used to
+    /// correlate the upstream reply, since several clients' requests share
+    /// this one socket and could otherwise collide).
+    pub async fn request(&self, chain_id: &str, upstream_uri: &str, mut body: Value) -> RpcResult<Value> {
+        let chain = self.chain(chain_id, upstream_uri).await?;
+        let original_id = body.get("id").cloned().unwrap_or(Value::Null);
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        body["id"] = json!(id);
+
+        let (sender, receiver) = oneshot::channel();
+        chain.pending.lock().await.insert(id, sender);
+        chain
+            .sink
+            .lock()
+            .await
+            .send(UpstreamMessage::Text(body.to_string()))
+            .await
+            .map_err(|e| RpcError::AxumTungstenite(Box::new(e)))?;
+
+        let mut response = receiver.await.map_err(|_| {
+            RpcError::AxumTungstenite(Box::new(async_tungstenite::tungstenite::Error::ConnectionClosed))
+        })?;
+        response["id"] = original_id;
+        Ok(response)
+    }
+}
+
+/// Drives a downstream client's WebSocket: `eth_subscribe`/`eth_unsubscribe`
+/// are multiplexed over the shared upstream connection for `chain_id`,
+/// everything else passes through transparently.
+pub async fn proxy(chain_id: String, upstream_uri: String, socket: WebSocket, multiplexer: Arc<SubscriptionMultiplexer>) {
+    let (mut client_sink, mut client_stream) = socket.split();
+    let (notify_sender, mut notify_receiver) = mpsc::unbounded_channel::<ClientMessage>();
+    let mut client_subscriptions: HashMap<String, Value> = HashMap::new();
+
+    loop {
+        tokio::select! {
+            notification = notify_receiver.recv() => {
+                let Some(notification) = notification else { break };
+                if client_sink.send(notification).await.is_err() {
+                    break;
+                }
+            }
+            message = client_stream.next() => {
+                let Some(Ok(ClientMessage::Text(text))) = message else { break };
+                let Ok(request) = serde_json::from_str::<Value>(&text) else { continue };
+                let request_id = request.get("id").cloned().unwrap_or(json!(null));
+                let method = request.get("method").and_then(Value::as_str).unwrap_or_default();
+
+                let response = match method {
+                    "eth_subscribe" => {
+                        let params = request.get("params").cloned().unwrap_or(json!([]));
+                        match multiplexer.subscribe(&chain_id, &upstream_uri, params.clone(), notify_sender.clone()).await {
+                            Ok(client_subscription_id) => {
+                                client_subscriptions.insert(client_subscription_id.to_string(), params);
+                                json!({"jsonrpc": "2.0", "id": request_id, "result": client_subscription_id.to_string()})
+                            }
+                            Err(e) => json!({"jsonrpc": "2.0", "id": request_id, "error": {"code": -32603, "message": e.to_string()}}),
+                        }
+                    }
+                    "eth_unsubscribe" => {
+                        let subscription_id = request
+                            .pointer("/params/0")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .to_string();
+                        if let Some(params) = client_subscriptions.remove(&subscription_id) {
+                            multiplexer.unsubscribe(&chain_id, &params, subscription_id.parse().unwrap_or_default()).await;
+                            json!({"jsonrpc": "2.0", "id": request_id, "result": true})
+                        } else {
+                            json!({"jsonrpc": "2.0", "id": request_id, "result": false})
+                        }
+                    }
+                    _ => match multiplexer.request(&chain_id, &upstream_uri, request).await {
+                        Ok(response) => response,
+                        Err(e) => json!({"jsonrpc": "2.0", "id": request_id, "error": {"code": -32603, "message": e.to_string()}}),
+                    },
+                };
+
+                if client_sink.send(ClientMessage::Text(response.to_string())).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    for (subscription_id, params) in client_subscriptions {
+        multiplexer
+            .unsubscribe(&chain_id, &params, subscription_id.parse().unwrap_or_default())
+            .await;
+    }
+}
+
+async fn read_upstream_loop(
+    chain: Arc<UpstreamChain>,
+    mut stream: futures::stream::SplitStream<WebSocketStream<ConnectStream>>,
+) {
+    while let Some(message) = stream.next().await {
+        let message = match message {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("upstream websocket read error: {e}");
+                break;
+            }
+        };
+        let UpstreamMessage::Text(text) = message else {
+            continue;
+        };
+        let Ok(value) = serde_json::from_str::<Value>(&text) else {
+            continue;
+        };
+
+        if let Some(method) = value.get("method").and_then(Value::as_str) {
+            if method == "eth_subscription" {
+                route_notification(&chain, &value).await;
+                continue;
+            }
+        }
+
+        let Some(id) = value.get("id").and_then(Value::as_u64) else {
+            continue;
+        };
+
+        if let Some(sender) = chain.pending.lock().await.remove(&id) {
+            let _ = sender.send(value);
+            continue;
+        }
+
+        // Otherwise this is the response to our `eth_subscribe` call: `id`
+        // is the client id that initiated it, `result` is the upstream
+        // subscription id.
+        if let Some(result) = value.get("result").and_then(Value::as_str) {
+            link_upstream_id(&chain, id, result).await;
+        }
+    }
+}
+
+async fn link_upstream_id(chain: &Arc<UpstreamChain>, client_id: u64, upstream_id: &str) {
+    let mut subscriptions = chain.subscriptions.lock().await;
+    if let Some(subscription) = subscriptions
+        .values_mut()
+        .find(|s| s.upstream_id.is_empty() && s.subscribers.contains_key(&client_id))
+    {
+        subscription.upstream_id = upstream_id.to_string();
+        let key = subscription_key(&subscription.params);
+        chain
+            .by_upstream_id
+            .lock()
+            .await
+            .insert(upstream_id.to_string(), key);
+    }
+}
+
+async fn route_notification(chain: &Arc<UpstreamChain>, value: &Value) {
+    let Some(upstream_id) = value
+        .pointer("/params/subscription")
+        .and_then(Value::as_str)
+    else {
+        return;
+    };
+    let Some(result) = value.pointer("/params/result") else {
+        return;
+    };
+
+    let key = {
+        let by_upstream_id = chain.by_upstream_id.lock().await;
+        let Some(key) = by_upstream_id.get(upstream_id) else {
+            return;
+        };
+        key.clone()
+    };
+
+    let subscriptions = chain.subscriptions.lock().await;
+    let Some(subscription) = subscriptions.get(&key) else {
+        return;
+    };
+
+    for (client_id, sender) in &subscription.subscribers {
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "eth_subscription",
+            "params": {
+                "subscription": client_id.to_string(),
+                "result": result,
+            },
+        });
+        let _ = sender.send(ClientMessage::Text(notification.to_string()));
+    }
+}